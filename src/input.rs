@@ -1,11 +1,54 @@
 use std::ffi::{OsStr, OsString};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::{Range, RangeInclusive};
 
+use chardetng::EncodingDetector;
 use content_inspector::{self, ContentType};
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
 
 use crate::error::*;
 
+/// A compression format recognized from the leading magic bytes of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompressionFormat {
+    Gzip,
+    Xz,
+    Bzip2,
+    Zstd,
+}
+
+impl CompressionFormat {
+    fn detect(bytes: &[u8]) -> Option<CompressionFormat> {
+        if bytes.starts_with(&[0x1F, 0x8B]) {
+            Some(CompressionFormat::Gzip)
+        } else if bytes.starts_with(&[0xFD, 0x37, 0x7A, 0x58, 0x5A]) {
+            Some(CompressionFormat::Xz)
+        } else if bytes.starts_with(&[0x42, 0x5A, 0x68]) {
+            Some(CompressionFormat::Bzip2)
+        } else if bytes.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(CompressionFormat::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Wraps `reader` in a decompressor for this format. Constructing the decompressor
+    /// can fail, and a stream that merely starts with a matching magic number isn't
+    /// guaranteed to be well-formed beyond that: a malformed stream (e.g. a truncated
+    /// zstd frame header) makes the first read from the returned reader fail. Callers
+    /// propagate either error rather than panicking.
+    fn decoder<'a>(self, reader: Box<dyn Read + 'a>) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            CompressionFormat::Gzip => Box::new(flate2::read::GzDecoder::new(reader)),
+            CompressionFormat::Xz => Box::new(xz2::read::XzDecoder::new(reader)),
+            CompressionFormat::Bzip2 => Box::new(bzip2::read::BzDecoder::new(reader)),
+            CompressionFormat::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        })
+    }
+}
+
 const THEME_PREVIEW_FILE: &[u8] = include_bytes!("../assets/theme_preview.rs");
 
 /// A description of an Input source.
@@ -64,6 +107,7 @@ pub(crate) enum InputKind<'a> {
     StdIn,
     ThemePreviewFile,
     CustomReader(Box<dyn Read + 'a>),
+    Url(String),
 }
 
 impl<'a> InputKind<'a> {
@@ -75,6 +119,7 @@ impl<'a> InputKind<'a> {
             InputKind::StdIn => InputDescription::new("STDIN"),
             InputKind::ThemePreviewFile => InputDescription::new(""),
             InputKind::CustomReader(_) => InputDescription::new("READER"),
+            InputKind::Url(ref url) => InputDescription::new(url.clone()).with_kind(Some("URL")),
         }
     }
 }
@@ -88,6 +133,11 @@ pub struct Input<'a> {
     pub(crate) kind: InputKind<'a>,
     pub(crate) metadata: InputMetadata,
     pub(crate) description: Option<InputDescription>,
+    pub(crate) encoding: Option<String>,
+    pub(crate) decompress: bool,
+    pub(crate) byte_range: Option<Range<u64>>,
+    pub(crate) line_range: Option<RangeInclusive<usize>>,
+    pub(crate) mmap: bool,
 }
 
 pub(crate) enum OpenedInputKind {
@@ -95,6 +145,7 @@ pub(crate) enum OpenedInputKind {
     StdIn,
     ThemePreviewFile,
     CustomReader,
+    Url(String),
 }
 
 impl OpenedInputKind {
@@ -111,6 +162,15 @@ pub(crate) struct OpenedInput<'a> {
     pub(crate) metadata: InputMetadata,
     pub(crate) reader: InputReader<'a>,
     pub(crate) description: InputDescription,
+    /// The encoding resolved for the stream (from an override, a BOM or content
+    /// sniffing), if any; `None` when none was resolved. The stream is transcoded only
+    /// when this is an encoding other than UTF-8. Mirrors `reader.encoding`; surfaced here too so callers can
+    /// display it without reaching into the reader.
+    pub(crate) encoding: Option<&'static Encoding>,
+    /// The compression format the stream was unwrapped from, if any. Mirrors
+    /// `reader.compression`; surfaced here too so callers can display it without
+    /// reaching into the reader.
+    pub(crate) compression: Option<CompressionFormat>,
 }
 
 impl<'a> Input<'a> {
@@ -119,6 +179,11 @@ impl<'a> Input<'a> {
             kind: InputKind::OrdinaryFile(path.to_os_string()),
             metadata: InputMetadata::default(),
             description: None,
+            encoding: None,
+            decompress: true,
+            byte_range: None,
+            line_range: None,
+            mmap: false,
         }
     }
 
@@ -127,6 +192,11 @@ impl<'a> Input<'a> {
             kind: InputKind::StdIn,
             metadata: InputMetadata::default(),
             description: None,
+            encoding: None,
+            decompress: true,
+            byte_range: None,
+            line_range: None,
+            mmap: false,
         }
     }
 
@@ -135,6 +205,11 @@ impl<'a> Input<'a> {
             kind: InputKind::ThemePreviewFile,
             metadata: InputMetadata::default(),
             description: None,
+            encoding: None,
+            decompress: true,
+            byte_range: None,
+            line_range: None,
+            mmap: false,
         }
     }
 
@@ -143,6 +218,29 @@ impl<'a> Input<'a> {
             kind: InputKind::CustomReader(reader),
             metadata: InputMetadata::default(),
             description: None,
+            encoding: None,
+            decompress: true,
+            byte_range: None,
+            line_range: None,
+            mmap: false,
+        }
+    }
+
+    /// Creates an input that streams the body of an `http(s)://` URL.
+    ///
+    /// The connection is established lazily, when the `Input` is opened; redirects are
+    /// followed automatically, and the same content-type/encoding detection used for
+    /// files and stdin applies to the response body.
+    pub fn from_url(url: &str) -> Self {
+        Input {
+            kind: InputKind::Url(url.to_string()),
+            metadata: InputMetadata::default(),
+            description: None,
+            encoding: None,
+            decompress: true,
+            byte_range: None,
+            line_range: None,
+            mmap: false,
         }
     }
 
@@ -164,6 +262,62 @@ impl<'a> Input<'a> {
         self
     }
 
+    /// Overrides automatic character-encoding detection with an explicit label
+    /// (e.g. `"utf-8"`, `"latin1"`, `"shift_jis"`), as understood by
+    /// `encoding_rs::Encoding::for_label`. When `None` (the default), the
+    /// encoding is auto-detected from a BOM, if any, in the input, or else guessed
+    /// from the content when it isn't valid UTF-8.
+    pub fn with_encoding(mut self, encoding: Option<&str>) -> Self {
+        self.encoding = encoding.map(|e| e.to_string());
+        self
+    }
+
+    /// Controls whether a compressed input (gzip, xz, bzip2, zstd) is transparently
+    /// decompressed. Enabled by default; pass `false` to see the raw compressed bytes.
+    pub fn with_decompression(mut self, decompress: bool) -> Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Restricts reading to a half-open byte range `[start, end)` of the underlying
+    /// stream. The semantics are byte-exact at `start`: if `start` falls in the middle
+    /// of a line, the first line `read_line` returns is truncated to just its suffix
+    /// from `start` onward, not the line in full. This holds regardless of how `start`
+    /// is reached -- for a seekable source read without compression or transcoding
+    /// (e.g. an ordinary file with neither applied), bytes before `start` are skipped
+    /// with a single seek and the same truncation happens as a side effect of starting
+    /// the read there; otherwise they are read and discarded line by line, with the
+    /// transitional line explicitly trimmed to match. Either way, `read_line` reports
+    /// EOF once `end` is reached, so callers can stop printing early.
+    pub fn with_byte_range(mut self, range: Option<Range<u64>>) -> Self {
+        self.byte_range = range;
+        self
+    }
+
+    /// Restricts reading to an inclusive, 1-based line range. Lines before the range
+    /// are read and discarded; `read_line` reports EOF once the last line in the range
+    /// has been returned.
+    pub fn with_line_range(mut self, range: Option<RangeInclusive<usize>>) -> Self {
+        self.line_range = range;
+        self
+    }
+
+    /// For `InputKind::OrdinaryFile`, memory-maps the file via `memmap2` instead of
+    /// going through a `BufReader<File>`. This benefits repeated paging over large
+    /// files by avoiding buffer copies in `read_line`. Disabled by default; falls back
+    /// to buffered reads for files that cannot be mapped (e.g. special files, empty
+    /// files), and has no effect on other input kinds.
+    ///
+    /// Caveat: if the file is truncated or rewritten by another process while it's
+    /// mapped, subsequent reads can raise SIGBUS and take the whole process down,
+    /// rather than surfacing as an `io::Error` the way a buffered read's failure would.
+    /// This is considered an acceptable tradeoff for opting in on an ordinary file, but
+    /// is worth knowing before enabling it for input you don't control.
+    pub fn with_mmap(mut self, mmap: bool) -> Self {
+        self.mmap = mmap;
+        self
+    }
+
     pub fn description(&self) -> InputDescription {
         if let Some(ref description) = self.description {
             description.clone()
@@ -176,52 +330,320 @@ impl<'a> Input<'a> {
 
     pub(crate) fn open<R: BufRead + 'a>(self, stdin: R) -> Result<OpenedInput<'a>> {
         let description = self.description().clone();
+        let encoding = self.encoding.as_deref();
+        let decompress = self.decompress;
+        let byte_range = self.byte_range.clone();
+        let line_range = self.line_range.clone();
         match self.kind {
-            InputKind::StdIn => Ok(OpenedInput {
-                kind: OpenedInputKind::StdIn,
-                description,
-                metadata: self.metadata,
-                reader: InputReader::new(stdin),
-            }),
-            InputKind::OrdinaryFile(path) => Ok(OpenedInput {
-                kind: OpenedInputKind::OrdinaryFile(path.clone()),
-                description,
-                metadata: self.metadata,
-                reader: {
-                    let file = File::open(&path)
-                        .map_err(|e| format!("'{}': {}", path.to_string_lossy(), e))?;
-                    if file.metadata()?.is_dir() {
-                        return Err(format!("'{}' is a directory.", path.to_string_lossy()).into());
+            InputKind::StdIn => {
+                let reader = InputReader::new(stdin, encoding, decompress, byte_range, line_range, 0)?;
+                Ok(OpenedInput {
+                    kind: OpenedInputKind::StdIn,
+                    description,
+                    metadata: self.metadata,
+                    encoding: reader.encoding,
+                    compression: reader.compression,
+                    reader,
+                })
+            }
+            InputKind::OrdinaryFile(path) => {
+                let file = File::open(&path)
+                    .map_err(|e| format!("'{}': {}", path.to_string_lossy(), e))?;
+                let file_metadata = file.metadata()?;
+                if file_metadata.is_dir() {
+                    return Err(format!("'{}' is a directory.", path.to_string_lossy()).into());
+                }
+
+                let reader = if self.mmap && file_metadata.len() > 0 {
+                    // SAFETY: `memmap2::Mmap::map` is unsafe because the kernel mapping
+                    // bypasses Rust's aliasing guarantees: if `file` is truncated or
+                    // rewritten by another process while we hold the mapping, later
+                    // reads through it can raise SIGBUS and abort the process instead
+                    // of returning an `io::Error`. This is an accepted, opt-in tradeoff
+                    // for `with_mmap` (see its doc comment) rather than unsound in the
+                    // memory-safety sense; no other thread or alias mutates `file`
+                    // through this handle.
+                    match unsafe { memmap2::Mmap::map(&file) } {
+                        Ok(mmap) => {
+                            let mut cursor = io::Cursor::new(mmap);
+                            let skipped = maybe_seek_to_byte_range_start(
+                                &mut cursor,
+                                encoding,
+                                decompress,
+                                &byte_range,
+                                &line_range,
+                            )?;
+                            InputReader::new(
+                                cursor, encoding, decompress, byte_range, line_range, skipped,
+                            )?
+                        }
+                        Err(_) => {
+                            let mut buffered = BufReader::new(file);
+                            let skipped = maybe_seek_to_byte_range_start(
+                                &mut buffered,
+                                encoding,
+                                decompress,
+                                &byte_range,
+                                &line_range,
+                            )?;
+                            InputReader::new(
+                                buffered, encoding, decompress, byte_range, line_range, skipped,
+                            )?
+                        }
                     }
-                    InputReader::new(BufReader::new(file))
-                },
-            }),
-            InputKind::ThemePreviewFile => Ok(OpenedInput {
-                kind: OpenedInputKind::ThemePreviewFile,
-                description,
-                metadata: self.metadata,
-                reader: InputReader::new(THEME_PREVIEW_FILE),
-            }),
-            InputKind::CustomReader(reader) => Ok(OpenedInput {
-                description,
-                kind: OpenedInputKind::CustomReader,
-                metadata: self.metadata,
-                reader: InputReader::new(BufReader::new(reader)),
-            }),
+                } else {
+                    let mut buffered = BufReader::new(file);
+                    let skipped = maybe_seek_to_byte_range_start(
+                        &mut buffered,
+                        encoding,
+                        decompress,
+                        &byte_range,
+                        &line_range,
+                    )?;
+                    InputReader::new(buffered, encoding, decompress, byte_range, line_range, skipped)?
+                };
+
+                Ok(OpenedInput {
+                    kind: OpenedInputKind::OrdinaryFile(path.clone()),
+                    description,
+                    metadata: self.metadata,
+                    encoding: reader.encoding,
+                    compression: reader.compression,
+                    reader,
+                })
+            }
+            InputKind::ThemePreviewFile => {
+                let reader = InputReader::new(
+                    THEME_PREVIEW_FILE,
+                    encoding,
+                    decompress,
+                    byte_range,
+                    line_range,
+                    0,
+                )?;
+                Ok(OpenedInput {
+                    kind: OpenedInputKind::ThemePreviewFile,
+                    description,
+                    metadata: self.metadata,
+                    encoding: reader.encoding,
+                    compression: reader.compression,
+                    reader,
+                })
+            }
+            InputKind::CustomReader(custom_reader) => {
+                let reader = InputReader::new(
+                    BufReader::new(custom_reader),
+                    encoding,
+                    decompress,
+                    byte_range,
+                    line_range,
+                    0,
+                )?;
+                Ok(OpenedInput {
+                    description,
+                    kind: OpenedInputKind::CustomReader,
+                    metadata: self.metadata,
+                    encoding: reader.encoding,
+                    compression: reader.compression,
+                    reader,
+                })
+            }
+            InputKind::Url(url) => {
+                let response = ureq::get(&url)
+                    .call()
+                    .map_err(|e| format!("'{}': {}", url, e))?;
+                let reader = InputReader::new(
+                    BufReader::new(response.into_reader()),
+                    encoding,
+                    decompress,
+                    byte_range,
+                    line_range,
+                    0,
+                )?;
+                Ok(OpenedInput {
+                    kind: OpenedInputKind::Url(url.clone()),
+                    description,
+                    metadata: self.metadata,
+                    encoding: reader.encoding,
+                    compression: reader.compression,
+                    reader,
+                })
+            }
         }
     }
 }
 
+/// Statistically guesses a non-UTF-8 encoding for input that carries no BOM, using
+/// `chardetng`. Only consulted when the peeked bytes are not valid UTF-8 (a sequence
+/// merely cut off at the end of the peeked bytes doesn't count) and `content_inspector`
+/// doesn't flag them as `BINARY` -- plain ASCII/UTF-8 text and binary content never
+/// reach the detector.
+fn detect_non_bom_encoding(peeked: &[u8]) -> Option<&'static Encoding> {
+    if peeked.is_empty() || content_inspector::inspect(peeked) == ContentType::BINARY {
+        return None;
+    }
+    match std::str::from_utf8(peeked) {
+        Err(e) if e.error_len().is_some() => {}
+        _ => return None,
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(peeked, true);
+    let (encoding, confident) = detector.guess_assess(None, true);
+
+    if confident && encoding != encoding_rs::UTF_8 {
+        Some(encoding)
+    } else {
+        None
+    }
+}
+
+/// Whether `peeked` (the leading bytes of a stream) would reach `InputReader` unchanged,
+/// i.e. with no decompression or transcoding step in between. Only such streams can
+/// have their byte offsets skipped with a plain seek -- any other transform changes
+/// which underlying byte a given output offset corresponds to. Mirrors the same
+/// encoding resolution `InputReader::new` performs (BOM, then the `chardetng` fallback,
+/// then an explicit override), since a sniffed non-BOM encoding transcodes the stream
+/// just as much as a BOM-based or overridden one does.
+fn is_passthrough(peeked: &[u8], encoding_override: Option<&str>, decompress: bool) -> bool {
+    let compression = if decompress {
+        CompressionFormat::detect(peeked)
+    } else {
+        None
+    };
+    if compression.is_some() {
+        return false;
+    }
+
+    let encoding = match encoding_override {
+        Some(label) => Encoding::for_label(label.as_bytes()),
+        None => Encoding::for_bom(peeked)
+            .map(|(encoding, _)| encoding)
+            .or_else(|| detect_non_bom_encoding(peeked)),
+    };
+
+    match encoding {
+        None => true,
+        Some(encoding) => encoding == encoding_rs::UTF_8,
+    }
+}
+
+/// Seeks a seekable reader past `byte_range`'s start, when doing so is safe, and
+/// returns how many bytes were skipped this way (`0` if none were). This is the cheap
+/// alternative to `InputReader::read_line` discarding its way there line by line; it
+/// only applies when there's no line range to honor as well (seeking forward loses the
+/// ability to count the lines skipped over) and the stream is a passthrough one, since
+/// compression and transcoding both break the 1:1 mapping between input and output
+/// byte offsets that a seek relies on.
+fn maybe_seek_to_byte_range_start<R: BufRead + Seek>(
+    reader: &mut R,
+    encoding_override: Option<&str>,
+    decompress: bool,
+    byte_range: &Option<Range<u64>>,
+    line_range: &Option<RangeInclusive<usize>>,
+) -> Result<u64> {
+    let range = match byte_range {
+        Some(range) => range,
+        None => return Ok(0),
+    };
+    if line_range.is_some() || range.start == 0 {
+        return Ok(0);
+    }
+
+    let peeked = reader.fill_buf().unwrap_or(&[]).to_vec();
+    if !is_passthrough(&peeked, encoding_override, decompress) {
+        return Ok(0);
+    }
+
+    reader.seek(SeekFrom::Start(range.start))?;
+    Ok(range.start)
+}
+
 pub(crate) struct InputReader<'a> {
     inner: Box<dyn BufRead + 'a>,
     pub(crate) first_line: Vec<u8>,
     pub(crate) content_type: Option<ContentType>,
+    pub(crate) encoding: Option<&'static Encoding>,
+    pub(crate) compression: Option<CompressionFormat>,
+    byte_range: Option<Range<u64>>,
+    line_range: Option<RangeInclusive<usize>>,
+    current_line: usize,
+    bytes_read: u64,
 }
 
 impl<'a> InputReader<'a> {
-    fn new<R: BufRead + 'a>(mut reader: R) -> InputReader<'a> {
+    /// Builds a reader that always yields UTF-8 bytes to the rest of the pipeline,
+    /// regardless of the source encoding or compression.
+    ///
+    /// `encoding_override` takes an explicit label (see `Input::with_encoding`). When
+    /// `None`, the encoding is auto-detected from a BOM in the first few bytes of the
+    /// stream, or failing that guessed from content that is neither valid UTF-8 nor
+    /// binary (see `detect_non_bom_encoding`); otherwise the bytes are passed through
+    /// unmodified (plain UTF-8 and binary content both fall into this case).
+    ///
+    /// When `decompress` is `true`, the raw stream is sniffed for a known compression
+    /// magic number first, and unwrapped before encoding detection and line splitting
+    /// ever see it.
+    ///
+    /// `byte_range` and `line_range` are enforced in `read_line`: lines/bytes outside
+    /// the range are read and discarded rather than returned, and `read_line` reports
+    /// EOF as soon as the range is exhausted so callers can stop early. `bytes_already_skipped`
+    /// tells the byte-range check how far into the stream `reader` already is, for
+    /// callers that skipped past `byte_range`'s start with a seek rather than relying on
+    /// this discard-as-you-go behavior.
+    fn new<R: BufRead + 'a>(
+        mut reader: R,
+        encoding_override: Option<&str>,
+        decompress: bool,
+        byte_range: Option<Range<u64>>,
+        line_range: Option<RangeInclusive<usize>>,
+        bytes_already_skipped: u64,
+    ) -> Result<InputReader<'a>> {
+        let compression = if decompress {
+            CompressionFormat::detect(reader.fill_buf().unwrap_or(&[]))
+        } else {
+            None
+        };
+
+        let mut inner: Box<dyn BufRead + 'a> = match compression {
+            Some(format) => Box::new(BufReader::new(
+                format
+                    .decoder(Box::new(reader) as Box<dyn Read + 'a>)
+                    .map_err(|e| format!("failed to decompress input: {}", e))?,
+            )),
+            None => Box::new(reader),
+        };
+
+        let peeked: &[u8] = match inner.fill_buf() {
+            Ok(peeked) => peeked,
+            // A malformed compressed stream fails on this first read.
+            Err(e) if compression.is_some() => {
+                return Err(format!("failed to decompress input: {}", e).into())
+            }
+            Err(_) => &[],
+        };
+        let bom_encoding = Encoding::for_bom(peeked).map(|(encoding, _)| encoding);
+        let sniffed_encoding = bom_encoding.or_else(|| detect_non_bom_encoding(peeked));
+
+        let encoding = match encoding_override {
+            Some(label) => Some(
+                Encoding::for_label(label.as_bytes())
+                    .ok_or_else(|| format!("unknown encoding: '{}'", label))?,
+            ),
+            None => sniffed_encoding,
+        };
+
+        let mut inner: Box<dyn BufRead + 'a> = match encoding {
+            Some(encoding) if encoding != encoding_rs::UTF_8 => Box::new(BufReader::new(
+                DecodeReaderBytesBuilder::new()
+                    .encoding(Some(encoding))
+                    .build(inner),
+            )),
+            _ => inner,
+        };
+
         let mut first_line = vec![];
-        reader.read_until(b'\n', &mut first_line).ok();
+        inner.read_until(b'\n', &mut first_line).ok();
 
         let content_type = if first_line.is_empty() {
             None
@@ -229,29 +651,72 @@ impl<'a> InputReader<'a> {
             Some(content_inspector::inspect(&first_line[..]))
         };
 
-        if content_type == Some(ContentType::UTF_16LE) {
-            reader.read_until(0x00, &mut first_line).ok();
-        }
-
-        InputReader {
-            inner: Box::new(reader),
+        Ok(InputReader {
+            inner,
             first_line,
             content_type,
-        }
+            encoding,
+            compression,
+            byte_range,
+            line_range,
+            current_line: 0,
+            bytes_read: bytes_already_skipped,
+        })
     }
 
     pub(crate) fn read_line(&mut self, buf: &mut Vec<u8>) -> io::Result<bool> {
-        if self.first_line.is_empty() {
-            let res = self.inner.read_until(b'\n', buf).map(|size| size > 0)?;
+        loop {
+            let got = if self.first_line.is_empty() {
+                self.inner.read_until(b'\n', buf).map(|size| size > 0)?
+            } else {
+                buf.append(&mut self.first_line);
+                true
+            };
 
-            if self.content_type == Some(ContentType::UTF_16LE) {
-                self.inner.read_until(0x00, buf).ok();
+            if !got {
+                return Ok(false);
             }
 
-            Ok(res)
-        } else {
-            buf.append(&mut self.first_line);
-            Ok(true)
+            self.current_line += 1;
+            self.bytes_read += buf.len() as u64;
+
+            if let Some(range) = &self.line_range {
+                if self.current_line < *range.start() {
+                    buf.clear();
+                    continue;
+                }
+                if self.current_line > *range.end() {
+                    buf.clear();
+                    return Ok(false);
+                }
+            }
+
+            if let Some(range) = &self.byte_range {
+                // Bytes read so far, not counting the line just read into `buf`.
+                let prior_bytes_read = self.bytes_read - buf.len() as u64;
+
+                if self.bytes_read <= range.start {
+                    buf.clear();
+                    continue;
+                }
+
+                // `start` falls inside this line: trim its prefix so the first
+                // returned line is byte-exact at `start`, whether we got here by
+                // discarding whole lines or by a seek that landed mid-line (in which
+                // case `prior_bytes_read` already equals `range.start`, so this is a
+                // no-op -- see `maybe_seek_to_byte_range_start`).
+                if prior_bytes_read < range.start {
+                    let trim = (range.start - prior_bytes_read) as usize;
+                    buf.drain(0..trim);
+                }
+
+                if self.bytes_read > range.end {
+                    buf.clear();
+                    return Ok(false);
+                }
+            }
+
+            return Ok(true);
         }
     }
 }
@@ -259,7 +724,7 @@ impl<'a> InputReader<'a> {
 #[test]
 fn basic() {
     let content = b"#!/bin/bash\necho hello";
-    let mut reader = InputReader::new(&content[..]);
+    let mut reader = InputReader::new(&content[..], None, true, None, None, 0).unwrap();
 
     assert_eq!(b"#!/bin/bash\n", &reader.first_line[..]);
 
@@ -287,24 +752,26 @@ fn basic() {
 
 #[test]
 fn utf16le() {
+    // BOM + "s\nd" encoded as UTF-16LE; transcoded to plain UTF-8 before splitting lines.
     let content = b"\xFF\xFE\x73\x00\x0A\x00\x64\x00";
-    let mut reader = InputReader::new(&content[..]);
+    let mut reader = InputReader::new(&content[..], None, true, None, None, 0).unwrap();
 
-    assert_eq!(b"\xFF\xFE\x73\x00\x0A\x00", &reader.first_line[..]);
+    assert_eq!(Some(encoding_rs::UTF_16LE), reader.encoding);
+    assert_eq!(b"s\n", &reader.first_line[..]);
 
     let mut buffer = vec![];
 
     let res = reader.read_line(&mut buffer);
     assert!(res.is_ok());
     assert_eq!(true, res.unwrap());
-    assert_eq!(b"\xFF\xFE\x73\x00\x0A\x00", &buffer[..]);
+    assert_eq!(b"s\n", &buffer[..]);
 
     buffer.clear();
 
     let res = reader.read_line(&mut buffer);
     assert!(res.is_ok());
     assert_eq!(true, res.unwrap());
-    assert_eq!(b"\x64\x00", &buffer[..]);
+    assert_eq!(b"d", &buffer[..]);
 
     buffer.clear();
 
@@ -313,3 +780,312 @@ fn utf16le() {
     assert_eq!(false, res.unwrap());
     assert!(buffer.is_empty());
 }
+
+#[test]
+fn encoding_override() {
+    // 0xE9 is "é" in latin1 but not valid UTF-8 on its own.
+    let content = b"\xe9\n";
+    let mut reader = InputReader::new(&content[..], Some("latin1"), true, None, None, 0).unwrap();
+
+    assert_eq!("é\n".as_bytes(), &reader.first_line[..]);
+}
+
+#[test]
+fn unknown_encoding_label_is_an_error() {
+    let content = b"hello\n";
+    let result = InputReader::new(&content[..], Some("not-a-real-encoding"), true, None, None, 0);
+    assert!(result.is_err());
+}
+
+#[test]
+fn detects_non_bom_encoding_from_content() {
+    // No BOM, but the byte pattern is only valid Windows-1252, not UTF-8, so chardetng
+    // should be consulted and should recognize it as such.
+    let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode(
+        "Le café de la vieille ville étincelle sous le ciel d'été, où l'on déguste \
+         une pâtisserie dorée près de la rivière avant de rentrer à la maison.\n",
+    );
+    let mut reader = InputReader::new(&encoded[..], None, true, None, None, 0).unwrap();
+
+    assert_eq!(Some(encoding_rs::WINDOWS_1252), reader.encoding);
+}
+
+#[test]
+fn gzip_decompression() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"line one\nline two").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut reader = InputReader::new(&compressed[..], None, true, None, None, 0).unwrap();
+    assert_eq!(Some(CompressionFormat::Gzip), reader.compression);
+
+    let mut buffer = vec![];
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"line one\n", &buffer[..]);
+
+    buffer.clear();
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"line two", &buffer[..]);
+}
+
+#[test]
+fn decompression_can_be_disabled() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"line one\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let reader = InputReader::new(&compressed[..], None, false, None, None, 0).unwrap();
+    assert_eq!(None, reader.compression);
+    assert_eq!(&compressed[..], &reader.first_line[..]);
+}
+
+#[test]
+fn corrupt_zstd_frame_header_is_an_error_not_a_panic() {
+    // The zstd magic number followed by garbage: a real frame header never follows, so
+    // opening the reader should fail instead of panicking.
+    let content = b"\x28\xB5\x2F\xFD\x00\x00\x00\x00";
+
+    let result = InputReader::new(&content[..], None, true, None, None, 0);
+    assert!(result.is_err());
+}
+
+#[test]
+fn line_range_restriction() {
+    let content = b"one\ntwo\nthree\nfour\nfive\n";
+    let mut reader = InputReader::new(&content[..], None, true, None, Some(2..=3), 0).unwrap();
+
+    let mut buffer = vec![];
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"two\n", &buffer[..]);
+
+    buffer.clear();
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"three\n", &buffer[..]);
+
+    buffer.clear();
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(false, res.unwrap());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn byte_range_restriction() {
+    // Four 3-byte lines; a byte range of [3, 9) should keep only lines two and three.
+    let content = b"aa\nbb\ncc\ndd\n";
+    let mut reader = InputReader::new(&content[..], None, true, Some(3..9), None, 0).unwrap();
+
+    let mut buffer = vec![];
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"bb\n", &buffer[..]);
+
+    buffer.clear();
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"cc\n", &buffer[..]);
+
+    buffer.clear();
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(false, res.unwrap());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn byte_range_start_truncates_mid_line_to_a_byte_exact_suffix() {
+    // Byte 6 of "Hello World\n" is the 'W' of "World" -- the discard path should trim
+    // the "Hello " prefix rather than returning the line in full.
+    let content = b"Hello World\nSecond line\n";
+    let mut reader = InputReader::new(&content[..], None, true, Some(6..24), None, 0).unwrap();
+
+    let mut buffer = vec![];
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"World\n", &buffer[..]);
+}
+
+#[test]
+fn byte_range_seek_and_discard_paths_agree_on_a_mid_line_start() {
+    // Same offset and content as `byte_range_start_truncates_mid_line_to_a_byte_exact_suffix`,
+    // but routed through `Input::open`'s seekable ordinary-file path -- the two must
+    // produce the same first line.
+    let path = std::env::temp_dir().join("bat-input-byte-range-seek-test.txt");
+    std::fs::write(&path, b"Hello World\nSecond line\n").unwrap();
+
+    let opened = Input::ordinary_file(path.as_os_str())
+        .with_byte_range(Some(6..24))
+        .open(io::empty())
+        .unwrap();
+    let mut reader = opened.reader;
+    let mut buffer = vec![];
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"World\n", &buffer[..]);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn byte_range_start_is_skipped_with_a_seek_when_passthrough() {
+    let mut cursor = io::Cursor::new(b"0123456789".to_vec());
+    let byte_range = Some(5..10);
+
+    let skipped =
+        maybe_seek_to_byte_range_start(&mut cursor, None, true, &byte_range, &None).unwrap();
+
+    assert_eq!(5, skipped);
+
+    let mut remaining = Vec::new();
+    cursor.read_to_end(&mut remaining).unwrap();
+    assert_eq!(b"56789", &remaining[..]);
+}
+
+#[test]
+fn byte_range_start_is_not_seeked_past_when_compressed() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"0123456789").unwrap();
+    let compressed = encoder.finish().unwrap();
+    let mut cursor = io::Cursor::new(compressed);
+    let byte_range = Some(5..10);
+
+    let skipped =
+        maybe_seek_to_byte_range_start(&mut cursor, None, true, &byte_range, &None).unwrap();
+
+    assert_eq!(0, skipped);
+    assert_eq!(0, cursor.position());
+}
+
+#[test]
+fn byte_range_start_is_not_seeked_past_when_a_line_range_is_also_set() {
+    let mut cursor = io::Cursor::new(b"0123456789".to_vec());
+    let byte_range = Some(5..10);
+    let line_range = Some(1..=2);
+
+    let skipped =
+        maybe_seek_to_byte_range_start(&mut cursor, None, true, &byte_range, &line_range).unwrap();
+
+    assert_eq!(0, skipped);
+    assert_eq!(0, cursor.position());
+}
+
+#[test]
+fn byte_range_start_is_not_seeked_past_when_a_sniffed_encoding_would_transcode() {
+    // No BOM, but chardetng should recognize this as Windows-1252, same as
+    // `detects_non_bom_encoding_from_content` -- `is_passthrough` must refuse the seek
+    // here too, not just for BOM-bearing or overridden encodings.
+    let (encoded, _, _) = encoding_rs::WINDOWS_1252.encode(
+        "Le café de la vieille ville étincelle sous le ciel d'été, où l'on déguste \
+         une pâtisserie dorée près de la rivière avant de rentrer à la maison.\n",
+    );
+    let mut cursor = io::Cursor::new(encoded.into_owned());
+    let byte_range = Some(5..10);
+
+    let skipped =
+        maybe_seek_to_byte_range_start(&mut cursor, None, true, &byte_range, &None).unwrap();
+
+    assert_eq!(0, skipped);
+    assert_eq!(0, cursor.position());
+}
+
+#[test]
+fn opened_input_surfaces_resolved_encoding_and_compression() {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(b"line one\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let opened = Input::stdin().open(io::Cursor::new(compressed)).unwrap();
+
+    assert_eq!(Some(CompressionFormat::Gzip), opened.compression);
+    assert_eq!(opened.reader.compression, opened.compression);
+    assert_eq!(opened.reader.encoding, opened.encoding);
+}
+
+#[test]
+fn from_url_surfaces_connection_errors() {
+    // Nothing listens on this loopback port, so the connection is refused immediately;
+    // `open` should surface that as a formatted error rather than panicking or hanging.
+    let err = Input::from_url("http://127.0.0.1:1/unreachable")
+        .open(io::empty())
+        .err()
+        .expect("connecting to a closed port should fail");
+
+    assert!(err.to_string().contains("127.0.0.1:1"));
+}
+
+#[test]
+fn mmap_reads_the_same_content_as_a_buffered_file() {
+    let path = std::env::temp_dir().join("bat-input-mmap-test.txt");
+    std::fs::write(&path, b"line one\nline two").unwrap();
+
+    let opened = Input::ordinary_file(path.as_os_str())
+        .with_mmap(true)
+        .open(io::empty())
+        .unwrap();
+    let mut reader = opened.reader;
+    let mut buffer = vec![];
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"line one\n", &buffer[..]);
+
+    buffer.clear();
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(true, res.unwrap());
+    assert_eq!(b"line two", &buffer[..]);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn mmap_falls_back_to_buffered_reads_for_an_empty_file() {
+    // `Input::open` only attempts to mmap non-empty files; an empty file should still
+    // open successfully via the buffered fallback, yielding no lines rather than
+    // failing.
+    let path = std::env::temp_dir().join("bat-input-mmap-empty-test.txt");
+    std::fs::write(&path, b"").unwrap();
+
+    let opened = Input::ordinary_file(path.as_os_str())
+        .with_mmap(true)
+        .open(io::empty())
+        .unwrap();
+    let mut reader = opened.reader;
+    let mut buffer = vec![];
+
+    let res = reader.read_line(&mut buffer);
+    assert!(res.is_ok());
+    assert_eq!(false, res.unwrap());
+    assert!(buffer.is_empty());
+
+    std::fs::remove_file(&path).ok();
+}